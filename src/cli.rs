@@ -1,18 +1,114 @@
+use std::collections::HashSet;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process;
 use std::time::Instant;
 
-use crate::convert::convert_heic_to_jpeg;
+use crate::convert::{convert_image, is_raw_extension, ConvertOptions, OutputFormat};
+use crate::report::{self, ConversionRecord, ConversionTotals, ReportFormat};
 
-/// Resolve the input path to a directory and list of HEIC files.
+/// Parsed command-line options for a conversion run.
+struct RunOptions {
+    input_path: String,
+    convert: ConvertOptions,
+    threads: Option<usize>,
+    report_format: Option<ReportFormat>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            input_path: ".".to_string(),
+            convert: ConvertOptions::default(),
+            threads: None,
+            report_format: None,
+        }
+    }
+}
+
+/// Parse CLI arguments (excluding argv[0]) into a `RunOptions`.
+fn parse_args(args: &[String]) -> Result<RunOptions, Box<dyn std::error::Error>> {
+    let mut options = RunOptions::default();
+    let mut path_given = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                let value = iter
+                    .next()
+                    .ok_or("--format requires a value (jpeg, png, webp, avif)")?;
+                options.convert.format = value.parse().map_err(|e: String| e)?;
+            }
+            "--threads" | "-j" => {
+                let value = iter.next().ok_or("--threads requires a value")?;
+                options.threads = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid thread count: {}", value))?,
+                );
+            }
+            "--quality" | "-q" => {
+                let value = iter.next().ok_or("--quality requires a value (1-100)")?;
+                let quality: u32 = value
+                    .parse()
+                    .map_err(|_| format!("invalid quality: {}", value))?;
+                if quality == 0 || quality > 100 {
+                    return Err(format!("quality must be between 1 and 100, got {}", quality).into());
+                }
+                options.convert.quality = quality as u8;
+            }
+            "--max-dimension" | "--resize" => {
+                let value = iter
+                    .next()
+                    .ok_or("--max-dimension requires a value (PX or WxH)")?;
+                options.convert.max_dimension = Some(parse_max_dimension(value)?);
+            }
+            "--strip-metadata" => {
+                options.convert.strip_metadata = true;
+            }
+            "--report" => {
+                let value = iter.next().ok_or("--report requires a value (json, csv)")?;
+                options.report_format = Some(value.parse().map_err(|e: String| e)?);
+            }
+            other if !path_given && !other.starts_with('-') => {
+                options.input_path = other.to_string();
+                path_given = true;
+            }
+            other => return Err(format!("unexpected argument: {}", other).into()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parse a `--max-dimension`/`--resize` value, accepting either a bare pixel
+/// count (`2048`) or a `WIDTHxHEIGHT` pair (`1920x1080`), in which case the
+/// longer edge is used as the limit.
+fn parse_max_dimension(value: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    if let Some((w, h)) = value.split_once(['x', 'X']) {
+        let width: u32 = w
+            .parse()
+            .map_err(|_| format!("invalid resize width: {}", w))?;
+        let height: u32 = h
+            .parse()
+            .map_err(|_| format!("invalid resize height: {}", h))?;
+        Ok(width.max(height))
+    } else {
+        value
+            .parse()
+            .map_err(|_| format!("invalid max dimension: {}", value).into())
+    }
+}
+
+/// Resolve the input path to a directory and list of input files.
 fn resolve_input(input_path: &str) -> Result<(PathBuf, Vec<PathBuf>), Box<dyn std::error::Error>> {
     let path = Path::new(input_path);
     let metadata = fs::metadata(path)?;
 
     if metadata.is_dir() {
-        let files = get_heic_files(path)?;
+        let files = get_input_files(path)?;
         Ok((path.to_path_buf(), files))
     } else {
         let parent = path.parent().unwrap_or(Path::new(".")).to_path_buf();
@@ -20,15 +116,15 @@ fn resolve_input(input_path: &str) -> Result<(PathBuf, Vec<PathBuf>), Box<dyn st
     }
 }
 
-/// Get all .heic files in a directory.
-fn get_heic_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+/// Get all HEIC and RAW camera files in a directory.
+fn get_input_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let mut files = Vec::new();
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
         if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext.eq_ignore_ascii_case("heic") {
+            if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+                if ext.eq_ignore_ascii_case("heic") || is_raw_extension(ext) {
                     files.push(path);
                 }
             }
@@ -38,17 +134,50 @@ fn get_heic_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>
     Ok(files)
 }
 
-/// Ensure the output JPEG directory exists.
-fn ensure_jpeg_dir(base_dir: &Path) -> PathBuf {
-    let jpeg_dir = base_dir.join("jpegs");
-    fs::create_dir_all(&jpeg_dir).expect("Failed to create jpegs directory");
-    jpeg_dir
+/// Ensure the output image directory exists.
+fn ensure_output_dir(base_dir: &Path) -> PathBuf {
+    let output_dir = base_dir.join("images");
+    fs::create_dir_all(&output_dir).expect("Failed to create images directory");
+    output_dir
 }
 
-/// Get the output JPEG path for a given HEIC file.
-fn jpeg_output_path(jpeg_dir: &Path, heic_path: &Path) -> PathBuf {
-    let stem = heic_path.file_stem().unwrap_or_default();
-    jpeg_dir.join(format!("{}.jpg", stem.to_string_lossy()))
+/// Find file stems shared by more than one input file, e.g. `IMG_0001.HEIC` and
+/// `IMG_0001.CR2` in the same mixed HEIC/RAW directory. Without disambiguation,
+/// such files would all resolve to the same output path and race to overwrite
+/// each other in `process_files`'s parallel conversion.
+fn collect_stem_collisions(input_files: &[PathBuf]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut collisions = HashSet::new();
+    for input_path in input_files {
+        let stem = input_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        if !seen.insert(stem.clone()) {
+            collisions.insert(stem);
+        }
+    }
+    collisions
+}
+
+/// Get the output path for a given input file in the requested format. When its
+/// stem collides with another input file's (see `collect_stem_collisions`), the
+/// source extension is folded into the output name to keep the two distinct.
+fn output_file_path(
+    output_dir: &Path,
+    input_path: &Path,
+    format: OutputFormat,
+    colliding_stems: &HashSet<String>,
+) -> PathBuf {
+    let stem = input_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+
+    if colliding_stems.contains(&stem) {
+        let source_ext = input_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        output_dir.join(format!("{}.{}.{}", stem, source_ext, format.extension()))
+    } else {
+        output_dir.join(format!("{}.{}", stem, format.extension()))
+    }
 }
 
 /// Format bytes into human-readable size.
@@ -67,14 +196,19 @@ fn human_readable_size(bytes: u64) -> String {
     }
 }
 
-/// Process all HEIC files, converting them to JPEG in parallel.
-fn process_files(heic_files: &[PathBuf], jpeg_dir: &Path) -> Vec<(String, Result<(), String>)> {
+/// Process all input files, converting them to the requested format in parallel.
+fn process_files(
+    input_files: &[PathBuf],
+    output_dir: &Path,
+    convert_options: &ConvertOptions,
+    colliding_stems: &HashSet<String>,
+) -> Vec<(String, Result<(), String>)> {
     use rayon::prelude::*;
 
-    heic_files
+    input_files
         .par_iter()
-        .map(|heic_path| {
-            let file_name = heic_path
+        .map(|input_path| {
+            let file_name = input_path
                 .file_name()
                 .unwrap_or_default()
                 .to_string_lossy()
@@ -82,8 +216,9 @@ fn process_files(heic_files: &[PathBuf], jpeg_dir: &Path) -> Vec<(String, Result
 
             println!("Processing file: {}", file_name);
 
-            let output_path = jpeg_output_path(jpeg_dir, heic_path);
-            let result = convert_heic_to_jpeg(heic_path, &output_path)
+            let output_path =
+                output_file_path(output_dir, input_path, convert_options.format, colliding_stems);
+            let result = convert_image(input_path, &output_path, convert_options)
                 .map_err(|e| format!("error details: {}", e));
 
             (file_name, result)
@@ -91,71 +226,131 @@ fn process_files(heic_files: &[PathBuf], jpeg_dir: &Path) -> Vec<(String, Result
         .collect()
 }
 
-/// Save conversion logs to a file in the JPEG directory.
-fn save_logs(
-    jpeg_dir: &Path,
+/// Build a per-file conversion record plus running totals, for both the
+/// human-readable log and the optional machine-parseable report.
+fn build_records(
     base_dir: &Path,
+    output_dir: &Path,
     results: &[(String, Result<(), String>)],
     duration: std::time::Duration,
-) {
-    let log_path = jpeg_dir.join("logs.txt");
-    let mut log_lines = Vec::new();
-    let mut total_heic_size: u64 = 0;
-    let mut total_jpeg_size: u64 = 0;
+    format: OutputFormat,
+    colliding_stems: &HashSet<String>,
+) -> (Vec<ConversionRecord>, ConversionTotals) {
+    let mut records = Vec::with_capacity(results.len());
+    let mut total_input_bytes: u64 = 0;
+    let mut total_output_bytes: u64 = 0;
 
     for (file_name, result) in results {
-        let heic_path = base_dir.join(file_name);
-        let jpeg_path = jpeg_output_path(jpeg_dir, &heic_path);
-
-        let heic_size = fs::metadata(&heic_path).map(|m| m.len()).unwrap_or(0);
-        let jpeg_size = fs::metadata(&jpeg_path).map(|m| m.len()).unwrap_or(0);
-
-        total_heic_size += heic_size;
-        total_jpeg_size += jpeg_size;
-
-        let stem = Path::new(file_name)
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy();
-
-        match result {
-            Ok(()) => {
-                log_lines.push(format!(
-                    "{} {} > Converted > jpegs/{}.jpg {}",
-                    file_name,
-                    human_readable_size(heic_size),
-                    stem,
-                    human_readable_size(jpeg_size)
-                ));
-            }
-            Err(e) => {
-                log_lines.push(format!("{} > Error: {}", file_name, e));
-            }
-        }
+        let input_path = base_dir.join(file_name);
+        let output_path = output_file_path(output_dir, &input_path, format, colliding_stems);
+
+        let input_bytes = fs::metadata(&input_path).map(|m| m.len()).unwrap_or(0);
+        let output_bytes = match result {
+            Ok(()) => fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        };
+
+        total_input_bytes += input_bytes;
+        total_output_bytes += output_bytes;
+
+        records.push(ConversionRecord {
+            source: file_name.clone(),
+            output_path: result
+                .is_ok()
+                .then(|| output_path.to_string_lossy().to_string()),
+            input_bytes,
+            output_bytes,
+            success: result.is_ok(),
+            message: result.as_ref().err().cloned(),
+        });
     }
 
     let file_count = results.len();
-    let avg_duration = if file_count > 0 {
+    let average_duration = if file_count > 0 {
         duration / file_count as u32
     } else {
         duration
     };
 
+    let totals = ConversionTotals {
+        file_count,
+        total_duration: duration,
+        average_duration,
+        total_input_bytes,
+        total_output_bytes,
+    };
+
+    (records, totals)
+}
+
+/// Save conversion logs to a file in the output directory.
+fn save_logs(
+    output_dir: &Path,
+    base_dir: &Path,
+    results: &[(String, Result<(), String>)],
+    duration: std::time::Duration,
+    format: OutputFormat,
+    report_format: Option<ReportFormat>,
+    colliding_stems: &HashSet<String>,
+) {
+    let (records, totals) =
+        build_records(base_dir, output_dir, results, duration, format, colliding_stems);
+
+    let mut log_lines = Vec::new();
+    for record in &records {
+        if record.success {
+            // Derive the displayed name from the actual output path (rather than
+            // re-deriving "{stem}.{ext}") so a disambiguated name is reflected here too.
+            let output_name = record
+                .output_path
+                .as_deref()
+                .and_then(|path| Path::new(path).file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            log_lines.push(format!(
+                "{} {} > Converted > images/{} {}",
+                record.source,
+                human_readable_size(record.input_bytes),
+                output_name,
+                human_readable_size(record.output_bytes)
+            ));
+        } else {
+            log_lines.push(format!(
+                "{} > Error: {}",
+                record.source,
+                record.message.as_deref().unwrap_or("unknown error")
+            ));
+        }
+    }
+
     log_lines.push(String::new());
-    log_lines.push(format!("{} Files", file_count));
-    log_lines.push(format!("Total Time Taken=={:?}", duration));
-    log_lines.push(format!("Average Time Per File=={:?}", avg_duration));
+    log_lines.push(format!("{} Files", totals.file_count));
+    log_lines.push(format!("Total Time Taken=={:?}", totals.total_duration));
     log_lines.push(format!(
-        "Total HEIC File Size=={}",
-        human_readable_size(total_heic_size)
+        "Average Time Per File=={:?}",
+        totals.average_duration
     ));
     log_lines.push(format!(
-        "Total JPEG Folder Size=={}",
-        human_readable_size(total_jpeg_size)
+        "Total Input File Size=={}",
+        human_readable_size(totals.total_input_bytes)
+    ));
+    log_lines.push(format!(
+        "Total {} Folder Size=={}",
+        format.to_string().to_uppercase(),
+        human_readable_size(totals.total_output_bytes)
     ));
 
     println!("Saving logs to logs.txt...");
-    fs::write(log_path, log_lines.join("\n")).expect("Failed to write log file");
+    fs::write(output_dir.join("logs.txt"), log_lines.join("\n"))
+        .expect("Failed to write log file");
+
+    if let Some(report_format) = report_format {
+        let report_path = output_dir.join(format!("report.{}", report_format.extension()));
+        println!("Saving report to report.{}...", report_format.extension());
+        fs::write(report_path, report::render(&records, totals, report_format))
+            .expect("Failed to write report file");
+    }
 }
 
 #[derive(Debug)]
@@ -165,7 +360,7 @@ impl Command {
     pub fn run(args: &[String]) -> Result<Command, Box<dyn std::error::Error>> {
         if args.len() < 2 {
             // Default to current directory
-            return Self::run_conversion(".");
+            return Self::run_conversion(RunOptions::default());
         }
 
         // Check for special flags
@@ -181,31 +376,45 @@ impl Command {
             _ => {}
         }
 
-        if args.len() > 2 {
-            return Err("too many arguments, expecting: heictojpeg [path]".into());
-        }
-
-        Self::run_conversion(&args[1])
+        let options = parse_args(&args[1..])?;
+        Self::run_conversion(options)
     }
 
-    fn run_conversion(input_path: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    fn run_conversion(options: RunOptions) -> Result<Command, Box<dyn std::error::Error>> {
         println!("Starting the program...");
 
-        let (base_dir, heic_files) = resolve_input(input_path)?;
+        let (base_dir, input_files) = resolve_input(&options.input_path)?;
 
-        if heic_files.is_empty() {
-            println!("No HEIC files found.");
+        if input_files.is_empty() {
+            println!("No input files found.");
             return Ok(Command {});
         }
 
-        println!("Found {} HEIC file(s)", heic_files.len());
+        println!("Found {} input file(s)", input_files.len());
+
+        if let Some(threads) = options.threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+                .map_err(|e| format!("failed to configure thread pool: {}", e))?;
+        }
+
+        let colliding_stems = collect_stem_collisions(&input_files);
 
-        let jpeg_dir = ensure_jpeg_dir(&base_dir);
+        let output_dir = ensure_output_dir(&base_dir);
         let start = Instant::now();
-        let results = process_files(&heic_files, &jpeg_dir);
+        let results = process_files(&input_files, &output_dir, &options.convert, &colliding_stems);
         let duration = start.elapsed();
 
-        save_logs(&jpeg_dir, &base_dir, &results, duration);
+        save_logs(
+            &output_dir,
+            &base_dir,
+            &results,
+            duration,
+            options.convert.format,
+            options.report_format,
+            &colliding_stems,
+        );
 
         let success_count = results.iter().filter(|(_, r)| r.is_ok()).count();
         let error_count = results.len() - success_count;
@@ -245,13 +454,16 @@ pub fn print_help() {
     println!("    {}{}{}", GREEN, env!("CARGO_PKG_VERSION"), RESET);
     println!();
     println!("{}{}USAGE:{}", BOLD, YELLOW, RESET);
-    println!("    {} heictojpeg [PATH]{}", GREEN, RESET);
+    println!("    {} heictojpeg [OPTIONS] [PATH]{}", GREEN, RESET);
     println!();
-    println!("     PATH: path to a directory of HEIC files or a single HEIC file");
+    println!("     PATH: path to a directory of HEIC/RAW files or a single HEIC/RAW file");
     println!("           (defaults to current directory if omitted)");
     println!();
-    println!("     Converted JPEG files are saved to a 'jpegs/' subdirectory");
-    println!("     alongside the source files. EXIF data is preserved.");
+    println!("     Converted files are saved to an 'images/' subdirectory");
+    println!("     alongside the source files. For HEIC input with --format");
+    println!("     jpeg/png, EXIF and ICC color profiles (plus XMP for jpeg) are");
+    println!("     preserved unless --strip-metadata is passed; webp/avif outputs");
+    println!("     and RAW input carry none.");
     println!();
     println!("{}{}OPTIONS:{}", BOLD, YELLOW, RESET);
     println!(
@@ -262,16 +474,40 @@ pub fn print_help() {
         "    {}  -h, --help{}       Print help information",
         GREEN, RESET
     );
+    println!(
+        "    {}  -f, --format{}     Output format: jpeg, png, webp, avif (default: jpeg)",
+        GREEN, RESET
+    );
+    println!(
+        "    {}  -j, --threads{}    Number of threads to use (default: all cores)",
+        GREEN, RESET
+    );
+    println!(
+        "    {}  -q, --quality{}    JPEG quality, 1-100 (default: 95)",
+        GREEN, RESET
+    );
+    println!(
+        "    {}  --max-dimension{}  Downscale so the longest edge is at most PX, or WxH (default: no resize)",
+        GREEN, RESET
+    );
+    println!(
+        "    {}  --strip-metadata{} Drop EXIF/ICC/XMP metadata from the output",
+        GREEN, RESET
+    );
+    println!(
+        "    {}  --report{}         Also write a machine-parseable report: json, csv",
+        GREEN, RESET
+    );
     println!();
     println!("{}{}EXAMPLES:{}", BOLD, YELLOW, RESET);
     println!(
-        "    {} # Convert all HEIC files in current directory{}",
+        "    {} # Convert all HEIC/RAW files in current directory{}",
         CYAN, RESET
     );
     println!("    {} heictojpeg{}", GREEN, RESET);
     println!();
     println!(
-        "    {} # Convert all HEIC files in a specific directory{}",
+        "    {} # Convert all HEIC/RAW files in a specific directory{}",
         CYAN, RESET
     );
     println!("    {} heictojpeg ~/Photos{}", GREEN, RESET);
@@ -279,6 +515,18 @@ pub fn print_help() {
     println!("    {} # Convert a single file{}", CYAN, RESET);
     println!("    {} heictojpeg photo.heic{}", GREEN, RESET);
     println!();
+    println!("    {} # Convert to WebP instead of JPEG{}", CYAN, RESET);
+    println!("    {} heictojpeg --format webp ~/Photos{}", GREEN, RESET);
+    println!();
+    println!(
+        "    {} # Downscale for the web at lower quality{}",
+        CYAN, RESET
+    );
+    println!(
+        "    {} heictojpeg --quality 80 --max-dimension 2048 ~/Photos{}",
+        GREEN, RESET
+    );
+    println!();
 }
 
 #[cfg(test)]
@@ -302,4 +550,81 @@ mod tests {
         let parts: Vec<&str> = version.split('.').collect();
         assert_eq!(parts.len(), 3);
     }
+
+    #[test]
+    fn test_parse_max_dimension_bare_pixel_count() {
+        assert_eq!(parse_max_dimension("2048").unwrap(), 2048);
+    }
+
+    #[test]
+    fn test_parse_max_dimension_width_by_height_uses_longer_edge() {
+        assert_eq!(parse_max_dimension("1920x1080").unwrap(), 1920);
+        assert_eq!(parse_max_dimension("1080X1920").unwrap(), 1920);
+    }
+
+    #[test]
+    fn test_parse_max_dimension_rejects_garbage() {
+        assert!(parse_max_dimension("not-a-number").is_err());
+        assert!(parse_max_dimension("1920xnope").is_err());
+    }
+
+    #[test]
+    fn test_parse_args_rejects_unknown_flag_instead_of_treating_it_as_path() {
+        let args: Vec<String> = vec!["--qualityy".to_string(), "80".to_string()];
+        let err = parse_args(&args).unwrap_err();
+        assert_eq!(err.to_string(), "unexpected argument: --qualityy");
+    }
+
+    #[test]
+    fn test_collect_stem_collisions_flags_shared_stems_only() {
+        let input_files = vec![
+            PathBuf::from("IMG_0001.HEIC"),
+            PathBuf::from("IMG_0001.CR2"),
+            PathBuf::from("IMG_0002.HEIC"),
+        ];
+        let collisions = collect_stem_collisions(&input_files);
+        assert!(collisions.contains("IMG_0001"));
+        assert!(!collisions.contains("IMG_0002"));
+    }
+
+    #[test]
+    fn test_output_file_path_disambiguates_colliding_stems_by_source_extension() {
+        let output_dir = Path::new("images");
+        let colliding = collect_stem_collisions(&[
+            PathBuf::from("IMG_0001.HEIC"),
+            PathBuf::from("IMG_0001.CR2"),
+        ]);
+
+        let heic_out = output_file_path(
+            output_dir,
+            Path::new("IMG_0001.HEIC"),
+            OutputFormat::Jpeg,
+            &colliding,
+        );
+        let raw_out = output_file_path(
+            output_dir,
+            Path::new("IMG_0001.CR2"),
+            OutputFormat::Jpeg,
+            &colliding,
+        );
+
+        assert_ne!(heic_out, raw_out);
+        assert_eq!(heic_out, output_dir.join("IMG_0001.heic.jpg"));
+        assert_eq!(raw_out, output_dir.join("IMG_0001.cr2.jpg"));
+    }
+
+    #[test]
+    fn test_output_file_path_leaves_non_colliding_stem_unchanged() {
+        let output_dir = Path::new("images");
+        let colliding: HashSet<String> = HashSet::new();
+
+        let out = output_file_path(
+            output_dir,
+            Path::new("IMG_0002.HEIC"),
+            OutputFormat::Jpeg,
+            &colliding,
+        );
+
+        assert_eq!(out, output_dir.join("IMG_0002.jpg"));
+    }
 }
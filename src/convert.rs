@@ -1,22 +1,186 @@
+use std::fmt;
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
 use image::codecs::jpeg::JpegEncoder;
-use img_parts::jpeg::Jpeg;
-use img_parts::ImageEXIF;
+use image::codecs::png::PngEncoder;
+use image::ImageEncoder;
+use img_parts::jpeg::markers::{APP0, APP1};
+use img_parts::jpeg::{Jpeg, JpegSegment};
+use img_parts::png::Png;
+use img_parts::{Bytes, ImageEXIF, ImageICC};
 use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
 
-/// Convert a HEIC file to JPEG, preserving EXIF data.
-pub fn convert_heic_to_jpeg(
+use crate::exif_orientation;
+
+/// The APP1 signature XMP packets are identified by, per the XMP spec.
+const XMP_SIGNATURE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Output image format for a conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Jpeg,
+    Png,
+    WebP,
+    Avif,
+}
+
+impl OutputFormat {
+    /// File extension (without the leading dot) used for this format's output path.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::Png => "png",
+            OutputFormat::WebP => "webp",
+            OutputFormat::Avif => "avif",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jpeg" | "jpg" => Ok(OutputFormat::Jpeg),
+            "png" => Ok(OutputFormat::Png),
+            "webp" => Ok(OutputFormat::WebP),
+            "avif" => Ok(OutputFormat::Avif),
+            other => Err(format!(
+                "unknown format '{}', expected one of: jpeg, png, webp, avif",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Jpeg => write!(f, "jpeg"),
+            OutputFormat::Png => write!(f, "png"),
+            OutputFormat::WebP => write!(f, "webp"),
+            OutputFormat::Avif => write!(f, "avif"),
+        }
+    }
+}
+
+/// RAW camera file extensions, matched case-insensitively.
+pub const RAW_EXTENSIONS: &[&str] = &[
+    "cr2", "nef", "arw", "dng", "raf", "rw2", "orf", "pef", "srw",
+];
+
+/// Whether `ext` names a RAW camera file format this tool can recognize as input.
+pub fn is_raw_extension(ext: &str) -> bool {
+    RAW_EXTENSIONS.iter().any(|raw_ext| ext.eq_ignore_ascii_case(raw_ext))
+}
+
+/// Per-conversion knobs threaded through from the CLI.
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertOptions {
+    pub format: OutputFormat,
+    /// JPEG quality, 1-100.
+    pub quality: u8,
+    /// Longest edge to downscale to, preserving aspect ratio. `None` keeps the
+    /// source resolution.
+    pub max_dimension: Option<u32>,
+    /// Drop EXIF/ICC/XMP metadata from the output instead of carrying it over.
+    pub strip_metadata: bool,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        ConvertOptions {
+            format: OutputFormat::Jpeg,
+            quality: 95,
+            max_dimension: None,
+            strip_metadata: false,
+        }
+    }
+}
+
+/// Metadata carried over from the source image into the converted output.
+#[derive(Debug, Clone, Default)]
+struct ImageMetadata {
+    exif: Option<Vec<u8>>,
+    icc: Option<Vec<u8>>,
+    xmp: Option<Vec<u8>>,
+}
+
+/// Convert a HEIC or RAW camera file to the requested output format. Metadata
+/// is carried over from HEIC sources only; RAW inputs are encoded without any.
+pub fn convert_image(
     input: &Path,
     output: &Path,
+    options: &ConvertOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ext = input
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    if is_raw_extension(ext) {
+        convert_raw(input, output, options)
+    } else {
+        convert_heic(input, output, options)
+    }
+}
+
+#[cfg(feature = "raw")]
+fn convert_raw(
+    input: &Path,
+    output: &Path,
+    options: &ConvertOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rgb_image = crate::raw::decode_raw_to_rgb(input)?;
+    let rgb_image = resize_to_fit(rgb_image, options.max_dimension);
+    encode_rgb_image(&rgb_image, ImageMetadata::default(), output, options)
+}
+
+#[cfg(not(feature = "raw"))]
+fn convert_raw(
+    _input: &Path,
+    _output: &Path,
+    _options: &ConvertOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("RAW input support requires building with `--features raw`".into())
+}
+
+/// Convert a HEIC file to the requested output format, preserving EXIF data.
+fn convert_heic(
+    input: &Path,
+    output: &Path,
+    options: &ConvertOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let lib_heif = LibHeif::new();
     let ctx = HeifContext::read_from_file(input.to_str().ok_or("Invalid input path")?)?;
     let handle = ctx.primary_image_handle()?;
 
-    // Extract EXIF metadata before decoding
-    let exif_data = extract_exif_from_heif(&handle);
+    // Read EXIF before deciding whether to keep it: the Orientation tag must be
+    // applied to the pixels regardless of --strip-metadata, since that flag only
+    // controls what gets *embedded* in the output, not how the image is decoded.
+    let raw_exif = extract_exif_from_heif(&handle);
+    let orientation = raw_exif
+        .as_deref()
+        .map(exif_orientation::read_orientation)
+        .unwrap_or(1);
+
+    let mut metadata = if options.strip_metadata {
+        ImageMetadata::default()
+    } else {
+        ImageMetadata {
+            exif: raw_exif,
+            icc: extract_icc_profile(&handle),
+            xmp: extract_xmp_from_heif(&handle),
+        }
+    };
+
+    // The Orientation tag is applied physically below and then normalized to 1
+    // so viewers that do honor it don't rotate an already-upright image again.
+    if let Some(exif) = metadata.exif.as_mut() {
+        exif_orientation::normalize_orientation(exif);
+    }
 
     // Decode to RGB
     let image = lib_heif.decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)?;
@@ -35,31 +199,161 @@ pub fn convert_heic_to_jpeg(
         rgb_data.extend_from_slice(&data[start..end]);
     }
 
-    // Encode to JPEG in memory
     let rgb_image =
         image::RgbImage::from_raw(width, height, rgb_data).ok_or("Failed to create RGB image")?;
-    let mut jpeg_bytes = Vec::new();
-    let encoder = JpegEncoder::new_with_quality(&mut jpeg_bytes, 95);
-    rgb_image.write_with_encoder(encoder)?;
+    let rgb_image = exif_orientation::apply_orientation(rgb_image, orientation);
+    let rgb_image = resize_to_fit(rgb_image, options.max_dimension);
 
-    // If we have EXIF data, inject it into the JPEG
-    if let Some(exif) = exif_data {
-        let mut jpeg = Jpeg::from_bytes(jpeg_bytes.into())?;
-        jpeg.set_exif(Some(exif.into()));
-        let mut output_bytes = Vec::new();
-        jpeg.encoder().write_to(&mut output_bytes)?;
-        fs::write(output, output_bytes)?;
-    } else {
+    encode_rgb_image(&rgb_image, metadata, output, options)
+}
+
+/// Downscale `image` so its longest edge is at most `max_dimension`, preserving
+/// aspect ratio. Leaves the image untouched if it already fits or no limit was given.
+fn resize_to_fit(image: image::RgbImage, max_dimension: Option<u32>) -> image::RgbImage {
+    let Some(max_dimension) = max_dimension else {
+        return image;
+    };
+
+    let (width, height) = (image.width(), image.height());
+    let longest_edge = width.max(height);
+    if longest_edge <= max_dimension {
+        return image;
+    }
+
+    let scale = max_dimension as f64 / longest_edge as f64;
+    let new_width = ((width as f64) * scale).round().max(1.0) as u32;
+    let new_height = ((height as f64) * scale).round().max(1.0) as u32;
+
+    image::imageops::resize(
+        &image,
+        new_width,
+        new_height,
+        image::imageops::FilterType::Lanczos3,
+    )
+}
+
+/// Encode a decoded RGB image to `output` in the requested format.
+///
+/// EXIF/ICC/XMP `metadata` is embedded for JPEG outputs and EXIF/ICC for PNG
+/// outputs via `img_parts` (PNG drops XMP). WebP and AVIF have no equivalent
+/// embedding support here, so `metadata` is dropped for those two formats; the
+/// `--help` usage text scopes the metadata-preservation guarantee accordingly.
+fn encode_rgb_image(
+    rgb_image: &image::RgbImage,
+    metadata: ImageMetadata,
+    output: &Path,
+    options: &ConvertOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match options.format {
+        OutputFormat::Jpeg => {
+            let mut bytes = Vec::new();
+            let encoder = JpegEncoder::new_with_quality(&mut bytes, options.quality);
+            rgb_image.write_with_encoder(encoder)?;
+            write_with_jpeg_metadata(bytes, metadata, output)
+        }
+        OutputFormat::Png => {
+            let mut bytes = Vec::new();
+            let encoder = PngEncoder::new(&mut bytes);
+            encoder.write_image(
+                rgb_image,
+                rgb_image.width(),
+                rgb_image.height(),
+                image::ColorType::Rgb8,
+            )?;
+            write_with_png_metadata(bytes, metadata, output)
+        }
+        OutputFormat::WebP => {
+            let dynamic = image::DynamicImage::ImageRgb8(rgb_image.clone());
+            dynamic.save_with_format(output, image::ImageFormat::WebP)?;
+            Ok(())
+        }
+        OutputFormat::Avif => {
+            let dynamic = image::DynamicImage::ImageRgb8(rgb_image.clone());
+            dynamic.save_with_format(output, image::ImageFormat::Avif)?;
+            Ok(())
+        }
+    }
+}
+
+/// Write encoded JPEG bytes to `output`, embedding EXIF, ICC and XMP via `img_parts`
+/// when present. ICC profiles over 64 KB are split across APP2 chunks by `img_parts`
+/// itself; XMP has no equivalent helper, so it's inserted as a raw APP1 segment.
+fn write_with_jpeg_metadata(
+    jpeg_bytes: Vec<u8>,
+    metadata: ImageMetadata,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if metadata.exif.is_none() && metadata.icc.is_none() && metadata.xmp.is_none() {
         fs::write(output, jpeg_bytes)?;
+        return Ok(());
+    }
+
+    let mut jpeg = Jpeg::from_bytes(jpeg_bytes.into())?;
+    if let Some(exif) = metadata.exif {
+        jpeg.set_exif(Some(exif.into()));
+    }
+    if let Some(icc) = metadata.icc {
+        jpeg.set_icc_profile(Some(icc.into()));
+    }
+    if let Some(xmp) = metadata.xmp {
+        insert_xmp_segment(&mut jpeg, &xmp);
     }
 
+    let mut output_bytes = Vec::new();
+    jpeg.encoder().write_to(&mut output_bytes)?;
+    fs::write(output, output_bytes)?;
     Ok(())
 }
 
+/// Insert an XMP APP1 segment at the correct position: after any existing JFIF
+/// (APP0) or Exif (APP1) segment, per the XMP-in-JPEG embedding convention, so
+/// Exif still precedes XMP rather than the other way around.
+fn insert_xmp_segment(jpeg: &mut Jpeg, xmp: &[u8]) {
+    let segments = jpeg.segments_mut();
+    let insert_at = segments
+        .iter()
+        .rposition(|segment| segment.marker() == APP0 || segment.marker() == APP1)
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    segments.insert(insert_at, xmp_segment(xmp));
+}
+
+/// Write encoded PNG bytes to `output`, embedding EXIF and ICC via `img_parts` when
+/// present. PNG has no well-established XMP carrier via `img_parts`, so XMP is dropped.
+fn write_with_png_metadata(
+    png_bytes: Vec<u8>,
+    metadata: ImageMetadata,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if metadata.exif.is_none() && metadata.icc.is_none() {
+        fs::write(output, png_bytes)?;
+        return Ok(());
+    }
+
+    let mut png = Png::from_bytes(png_bytes.into())?;
+    if let Some(exif) = metadata.exif {
+        png.set_exif(Some(exif.into()));
+    }
+    if let Some(icc) = metadata.icc {
+        png.set_icc_profile(Some(icc.into()));
+    }
+
+    let mut output_bytes = Vec::new();
+    png.encoder().write_to(&mut output_bytes)?;
+    fs::write(output, output_bytes)?;
+    Ok(())
+}
+
+/// Build a raw APP1 segment carrying an XMP packet, per the XMP spec's embedding rules.
+fn xmp_segment(xmp: &[u8]) -> JpegSegment {
+    let mut contents = Vec::with_capacity(XMP_SIGNATURE.len() + xmp.len());
+    contents.extend_from_slice(XMP_SIGNATURE);
+    contents.extend_from_slice(xmp);
+    JpegSegment::new_with_contents(APP1, Bytes::from(contents))
+}
+
 /// Extract EXIF data from a HEIF image handle.
-fn extract_exif_from_heif(
-    handle: &libheif_rs::ImageHandle,
-) -> Option<Vec<u8>> {
+fn extract_exif_from_heif(handle: &libheif_rs::ImageHandle) -> Option<Vec<u8>> {
     let exif_fourcc: four_cc::FourCC = four_cc::FourCC(*b"Exif");
     let count = handle.number_of_metadata_blocks(exif_fourcc) as usize;
     if count == 0 {
@@ -70,7 +364,7 @@ fn extract_exif_from_heif(
     handle.metadata_block_ids(&mut ids, exif_fourcc);
 
     handle.metadata(ids[0]).ok().map(|data| {
-        // libheif EXIF metadata has a 4-byte offset prefix â€” skip it
+        // libheif EXIF metadata has a 4-byte offset prefix — skip it
         if data.len() > 4 {
             data[4..].to_vec()
         } else {
@@ -78,3 +372,99 @@ fn extract_exif_from_heif(
         }
     })
 }
+
+/// Extract the embedded ICC color profile from a HEIF image handle, if any.
+fn extract_icc_profile(handle: &libheif_rs::ImageHandle) -> Option<Vec<u8>> {
+    handle.color_profile_raw().map(|profile| profile.data)
+}
+
+/// Extract embedded XMP metadata from a HEIF image handle, if any.
+fn extract_xmp_from_heif(handle: &libheif_rs::ImageHandle) -> Option<Vec<u8>> {
+    let mime_fourcc: four_cc::FourCC = four_cc::FourCC(*b"mime");
+    let count = handle.number_of_metadata_blocks(mime_fourcc) as usize;
+    if count == 0 {
+        return None;
+    }
+
+    let mut ids = vec![0u32; count];
+    handle.metadata_block_ids(&mut ids, mime_fourcc);
+
+    handle.metadata(ids[0]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!("jpeg".parse(), Ok(OutputFormat::Jpeg));
+        assert_eq!("JPG".parse(), Ok(OutputFormat::Jpeg));
+        assert_eq!("png".parse(), Ok(OutputFormat::Png));
+        assert_eq!("WebP".parse(), Ok(OutputFormat::WebP));
+        assert_eq!("avif".parse(), Ok(OutputFormat::Avif));
+        assert!("tiff".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_is_raw_extension_matches_case_insensitively() {
+        assert!(is_raw_extension("cr2"));
+        assert!(is_raw_extension("NEF"));
+        assert!(is_raw_extension("Dng"));
+        assert!(!is_raw_extension("heic"));
+        assert!(!is_raw_extension("jpg"));
+    }
+
+    #[test]
+    fn test_resize_to_fit_noop_when_no_limit() {
+        let image = image::RgbImage::new(800, 600);
+        let resized = resize_to_fit(image, None);
+        assert_eq!((resized.width(), resized.height()), (800, 600));
+    }
+
+    #[test]
+    fn test_resize_to_fit_noop_when_already_within_limit() {
+        let image = image::RgbImage::new(800, 600);
+        let resized = resize_to_fit(image, Some(1000));
+        assert_eq!((resized.width(), resized.height()), (800, 600));
+    }
+
+    #[test]
+    fn test_resize_to_fit_downscales_preserving_aspect_ratio() {
+        let image = image::RgbImage::new(800, 400);
+        let resized = resize_to_fit(image, Some(400));
+        assert_eq!((resized.width(), resized.height()), (400, 200));
+    }
+
+    #[test]
+    fn test_insert_xmp_segment_lands_after_existing_app1_segment() {
+        let image = image::RgbImage::from_pixel(2, 2, image::Rgb([128, 128, 128]));
+        let mut jpeg_bytes = Vec::new();
+        let encoder = JpegEncoder::new_with_quality(&mut jpeg_bytes, 90);
+        image.write_with_encoder(encoder).unwrap();
+
+        let mut jpeg = Jpeg::from_bytes(jpeg_bytes.into()).unwrap();
+
+        // Stand in for the Exif segment `set_exif` would have just placed.
+        let exif_idx = jpeg.segments().len();
+        jpeg.segments_mut().push(JpegSegment::new_with_contents(
+            APP1,
+            Bytes::from_static(b"Exif\0\0fake-exif"),
+        ));
+
+        insert_xmp_segment(&mut jpeg, b"fake-xmp-packet");
+
+        let xmp_idx = jpeg
+            .segments()
+            .iter()
+            .position(|segment| segment.marker() == APP1 && segment.contents().starts_with(XMP_SIGNATURE))
+            .expect("xmp segment should have been inserted");
+
+        assert!(
+            xmp_idx > exif_idx,
+            "xmp segment (at {}) must come after the existing Exif segment (at {})",
+            xmp_idx,
+            exif_idx
+        );
+    }
+}
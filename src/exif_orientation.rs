@@ -0,0 +1,224 @@
+//! Parsing and application of the EXIF Orientation tag (IFD0 0x0112).
+
+use image::RgbImage;
+
+const ORIENTATION_TAG: u16 = 0x0112;
+
+fn read_u16(data: &[u8], offset: usize, little_endian: bool) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(if little_endian {
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    } else {
+        u16::from_be_bytes([bytes[0], bytes[1]])
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize, little_endian: bool) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(if little_endian {
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    } else {
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    })
+}
+
+/// Locate the byte offset of the Orientation entry's value field within `exif`,
+/// along with its current value and the buffer's byte order. `None` if the tag
+/// is absent, the data is truncated, or the byte-order marker is unrecognized.
+fn find_orientation_entry(exif: &[u8]) -> Option<(usize, u16, bool)> {
+    if exif.len() < 8 {
+        return None;
+    }
+
+    let little_endian = match &exif[0..2] {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+
+    let ifd0_offset = read_u32(exif, 4, little_endian)? as usize;
+    let entry_count = read_u16(exif, ifd0_offset, little_endian)? as usize;
+    let entries_start = ifd0_offset + 2;
+
+    for i in 0..entry_count {
+        let entry_offset = entries_start + i * 12;
+        let tag = read_u16(exif, entry_offset, little_endian)?;
+        if tag == ORIENTATION_TAG {
+            let value_offset = entry_offset + 8;
+            let value = read_u16(exif, value_offset, little_endian)?;
+            return Some((value_offset, value, little_endian));
+        }
+    }
+
+    None
+}
+
+/// Read the EXIF Orientation value (1-8), defaulting to `1` (no transform
+/// needed) if the tag is absent or the data can't be parsed.
+pub fn read_orientation(exif: &[u8]) -> u16 {
+    find_orientation_entry(exif)
+        .map(|(_, value, _)| value)
+        .unwrap_or(1)
+}
+
+/// Rewrite the Orientation tag in `exif` to `1` in place, so downstream viewers
+/// don't re-apply a rotation this crate has already baked into the pixels.
+/// No-op if the tag is absent.
+pub fn normalize_orientation(exif: &mut [u8]) {
+    if let Some((value_offset, _, little_endian)) = find_orientation_entry(exif) {
+        let bytes = if little_endian {
+            1u16.to_le_bytes()
+        } else {
+            1u16.to_be_bytes()
+        };
+        exif[value_offset..value_offset + 2].copy_from_slice(&bytes);
+    }
+}
+
+/// Physically apply the EXIF Orientation transform to `image` so it displays
+/// upright without relying on viewers honoring the Orientation tag.
+pub fn apply_orientation(image: RgbImage, orientation: u16) -> RgbImage {
+    use image::imageops::{flip_horizontal, flip_vertical, rotate180, rotate270, rotate90};
+
+    match orientation {
+        2 => flip_horizontal(&image),
+        3 => rotate180(&image),
+        4 => flip_vertical(&image),
+        5 => flip_horizontal(&rotate90(&image)),
+        6 => rotate90(&image),
+        7 => flip_horizontal(&rotate270(&image)),
+        8 => rotate270(&image),
+        _ => image,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a minimal single-entry TIFF/EXIF buffer containing just an
+    /// Orientation tag, for either byte order.
+    fn exif_with_orientation(little_endian: bool, orientation: u16) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(if little_endian { b"II" } else { b"MM" });
+        let put_u16 = |buf: &mut Vec<u8>, v: u16| {
+            if little_endian {
+                buf.extend_from_slice(&v.to_le_bytes());
+            } else {
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+        };
+        let put_u32 = |buf: &mut Vec<u8>, v: u32| {
+            if little_endian {
+                buf.extend_from_slice(&v.to_le_bytes());
+            } else {
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+        };
+
+        put_u16(&mut buf, 42); // TIFF magic
+        put_u32(&mut buf, 8); // IFD0 offset
+        put_u16(&mut buf, 1); // one entry
+        put_u16(&mut buf, 0x0112); // Orientation tag
+        put_u16(&mut buf, 3); // type SHORT
+        put_u32(&mut buf, 1); // count
+        put_u16(&mut buf, orientation); // value (first 2 bytes of the 4-byte field)
+        put_u16(&mut buf, 0); // padding to fill the 4-byte value field
+        put_u32(&mut buf, 0); // next IFD offset
+
+        buf
+    }
+
+    #[test]
+    fn test_read_orientation_little_endian() {
+        let exif = exif_with_orientation(true, 6);
+        assert_eq!(read_orientation(&exif), 6);
+    }
+
+    #[test]
+    fn test_read_orientation_big_endian() {
+        let exif = exif_with_orientation(false, 8);
+        assert_eq!(read_orientation(&exif), 8);
+    }
+
+    #[test]
+    fn test_read_orientation_defaults_to_one_when_absent() {
+        assert_eq!(read_orientation(&[]), 1);
+        assert_eq!(read_orientation(b"II"), 1);
+    }
+
+    #[test]
+    fn test_normalize_orientation_resets_to_one() {
+        let mut exif = exif_with_orientation(true, 6);
+        normalize_orientation(&mut exif);
+        assert_eq!(read_orientation(&exif), 1);
+    }
+
+    const RED: image::Rgb<u8> = image::Rgb([255, 0, 0]);
+    const GREEN: image::Rgb<u8> = image::Rgb([0, 255, 0]);
+    const BLUE: image::Rgb<u8> = image::Rgb([0, 0, 255]);
+    const YELLOW: image::Rgb<u8> = image::Rgb([255, 255, 0]);
+
+    /// A 3x2 image with a distinct color in each corner (top-left=red,
+    /// top-right=green, bottom-left=blue, bottom-right=yellow), asymmetric
+    /// enough to catch both a wrong flip/rotate axis and a dropped width/height
+    /// swap for the 90-degree orientations.
+    fn corner_test_image() -> RgbImage {
+        let mut image = RgbImage::from_pixel(3, 2, image::Rgb([0, 0, 0]));
+        image.put_pixel(0, 0, RED);
+        image.put_pixel(2, 0, GREEN);
+        image.put_pixel(0, 1, BLUE);
+        image.put_pixel(2, 1, YELLOW);
+        image
+    }
+
+    #[test]
+    fn test_apply_orientation_2_flips_horizontal() {
+        let out = apply_orientation(corner_test_image(), 2);
+        assert_eq!((out.width(), out.height()), (3, 2));
+        assert_eq!(*out.get_pixel(0, 0), GREEN);
+        assert_eq!(*out.get_pixel(2, 0), RED);
+        assert_eq!(*out.get_pixel(0, 1), YELLOW);
+        assert_eq!(*out.get_pixel(2, 1), BLUE);
+    }
+
+    #[test]
+    fn test_apply_orientation_6_rotates_90_clockwise() {
+        let out = apply_orientation(corner_test_image(), 6);
+        assert_eq!((out.width(), out.height()), (2, 3));
+        assert_eq!(*out.get_pixel(0, 0), BLUE);
+        assert_eq!(*out.get_pixel(1, 0), RED);
+        assert_eq!(*out.get_pixel(0, 2), YELLOW);
+        assert_eq!(*out.get_pixel(1, 2), GREEN);
+    }
+
+    #[test]
+    fn test_apply_orientation_8_rotates_270_clockwise() {
+        let out = apply_orientation(corner_test_image(), 8);
+        assert_eq!((out.width(), out.height()), (2, 3));
+        assert_eq!(*out.get_pixel(0, 0), GREEN);
+        assert_eq!(*out.get_pixel(1, 0), YELLOW);
+        assert_eq!(*out.get_pixel(0, 2), RED);
+        assert_eq!(*out.get_pixel(1, 2), BLUE);
+    }
+
+    #[test]
+    fn test_apply_orientation_5_transposes_main_diagonal() {
+        let out = apply_orientation(corner_test_image(), 5);
+        assert_eq!((out.width(), out.height()), (2, 3));
+        assert_eq!(*out.get_pixel(0, 0), RED);
+        assert_eq!(*out.get_pixel(1, 0), BLUE);
+        assert_eq!(*out.get_pixel(0, 2), GREEN);
+        assert_eq!(*out.get_pixel(1, 2), YELLOW);
+    }
+
+    #[test]
+    fn test_apply_orientation_7_transposes_anti_diagonal() {
+        let out = apply_orientation(corner_test_image(), 7);
+        assert_eq!((out.width(), out.height()), (2, 3));
+        assert_eq!(*out.get_pixel(0, 0), YELLOW);
+        assert_eq!(*out.get_pixel(1, 0), GREEN);
+        assert_eq!(*out.get_pixel(0, 2), BLUE);
+        assert_eq!(*out.get_pixel(1, 2), RED);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod cli;
+pub mod convert;
+mod exif_orientation;
+#[cfg(feature = "raw")]
+pub mod raw;
+pub mod report;
@@ -0,0 +1,14 @@
+use std::path::Path;
+
+use imagepipe::{ImageSource, Pipeline};
+
+/// Decode a RAW camera file (CR2, NEF, ARW, DNG, ...) into an 8-bit RGB image
+/// by running it through `rawloader` and `imagepipe`'s default processing pipeline.
+pub fn decode_raw_to_rgb(input: &Path) -> Result<image::RgbImage, Box<dyn std::error::Error>> {
+    let raw_image = rawloader::decode_file(input)?;
+    let mut pipeline = Pipeline::new_from_source(ImageSource::Raw(raw_image))?;
+    let decoded = pipeline.output_8bit(None)?;
+
+    image::RgbImage::from_raw(decoded.width as u32, decoded.height as u32, decoded.data)
+        .ok_or_else(|| "Failed to build RGB image from RAW pipeline output".into())
+}
@@ -0,0 +1,228 @@
+//! Machine-parseable (JSON/CSV) rendering of a conversion run, for scripting
+//! pipelines that need the same data `cli::save_logs` writes to `logs.txt`.
+
+/// Per-file result, one entry per source file in a conversion run.
+#[derive(Debug, Clone)]
+pub struct ConversionRecord {
+    pub source: String,
+    pub output_path: Option<String>,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// Aggregate totals across a conversion run.
+#[derive(Debug, Clone, Copy)]
+pub struct ConversionTotals {
+    pub file_count: usize,
+    pub total_duration: std::time::Duration,
+    pub average_duration: std::time::Duration,
+    pub total_input_bytes: u64,
+    pub total_output_bytes: u64,
+}
+
+/// Report output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    /// File extension (without the leading dot) for this report format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            other => Err(format!(
+                "unknown report format '{}', expected json or csv",
+                other
+            )),
+        }
+    }
+}
+
+/// Render `records`/`totals` as `format`, ready to write to a report file.
+pub fn render(records: &[ConversionRecord], totals: ConversionTotals, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => render_json(records, totals),
+        // CSV has no clean way to carry both per-file rows and aggregate totals
+        // in one parseable table, and the totals are already in logs.txt, so the
+        // CSV report sticks to one row per file.
+        ReportFormat::Csv => render_csv(records),
+    }
+}
+
+fn compression_ratio(record: &ConversionRecord) -> Option<f64> {
+    if !record.success || record.input_bytes == 0 {
+        None
+    } else {
+        Some(record.output_bytes as f64 / record.input_bytes as f64)
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn json_string_or_null(value: Option<&str>) -> String {
+    match value {
+        Some(s) => format!("\"{}\"", json_escape(s)),
+        None => "null".to_string(),
+    }
+}
+
+fn render_json(records: &[ConversionRecord], totals: ConversionTotals) -> String {
+    let files: Vec<String> = records
+        .iter()
+        .map(|record| {
+            let ratio = compression_ratio(record)
+                .map(|r| format!("{:.4}", r))
+                .unwrap_or_else(|| "null".to_string());
+
+            format!(
+                "    {{\n      \"source\": \"{}\",\n      \"output_path\": {},\n      \"input_bytes\": {},\n      \"output_bytes\": {},\n      \"compression_ratio\": {},\n      \"success\": {},\n      \"message\": {}\n    }}",
+                json_escape(&record.source),
+                json_string_or_null(record.output_path.as_deref()),
+                record.input_bytes,
+                record.output_bytes,
+                ratio,
+                record.success,
+                json_string_or_null(record.message.as_deref()),
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"files\": [\n{}\n  ],\n  \"summary\": {{\n    \"file_count\": {},\n    \"total_time_ms\": {},\n    \"average_time_ms\": {},\n    \"total_input_bytes\": {},\n    \"total_output_bytes\": {}\n  }}\n}}",
+        files.join(",\n"),
+        totals.file_count,
+        totals.total_duration.as_millis(),
+        totals.average_duration.as_millis(),
+        totals.total_input_bytes,
+        totals.total_output_bytes,
+    )
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// Aggregate totals are deliberately left out of the CSV body: a trailing
+// free-text summary row would need the same column count (or unescaped
+// commas) as the per-file rows above it, which breaks real CSV readers.
+// Totals are already written to logs.txt and the JSON report.
+fn render_csv(records: &[ConversionRecord]) -> String {
+    let mut lines = vec![
+        "source,output_path,input_bytes,output_bytes,compression_ratio,success,message"
+            .to_string(),
+    ];
+
+    for record in records {
+        let ratio = compression_ratio(record)
+            .map(|r| format!("{:.4}", r))
+            .unwrap_or_default();
+
+        lines.push(format!(
+            "{},{},{},{},{},{},{}",
+            csv_escape(&record.source),
+            csv_escape(record.output_path.as_deref().unwrap_or("")),
+            record.input_bytes,
+            record.output_bytes,
+            ratio,
+            record.success,
+            csv_escape(record.message.as_deref().unwrap_or("")),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(success: bool, input_bytes: u64, output_bytes: u64) -> ConversionRecord {
+        ConversionRecord {
+            source: "in.heic".to_string(),
+            output_path: Some("images/in.jpg".to_string()),
+            input_bytes,
+            output_bytes,
+            success,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_compression_ratio_none_when_input_empty() {
+        assert_eq!(compression_ratio(&record(true, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_compression_ratio_none_when_failed() {
+        // build_records forces output_bytes to 0 on failure, which would
+        // otherwise read as a (misleading) 100% size reduction.
+        assert_eq!(compression_ratio(&record(false, 1000, 0)), None);
+    }
+
+    #[test]
+    fn test_compression_ratio_computed_on_success() {
+        assert_eq!(compression_ratio(&record(true, 1000, 250)), Some(0.25));
+    }
+
+    #[test]
+    fn test_json_escape_handles_quotes_and_control_chars() {
+        assert_eq!(json_escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_json_string_or_null() {
+        assert_eq!(json_string_or_null(Some("x")), "\"x\"");
+        assert_eq!(json_string_or_null(None), "null");
+    }
+
+    #[test]
+    fn test_csv_escape_quotes_fields_with_special_chars() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_render_csv_has_no_trailing_summary_row() {
+        let records = vec![record(true, 1000, 250), record(false, 500, 0)];
+        let csv = render_csv(&records);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(!lines.iter().any(|line| line.starts_with('#')));
+        assert!(lines[2].ends_with(",false,"));
+    }
+}